@@ -0,0 +1,50 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Result};
+
+/// Mirrors the unnamed register to an external clipboard, e.g. via
+/// `wl-copy`/`wl-paste`, `pbcopy`/`pbpaste`, or `xclip`.
+pub trait ClipboardProvider {
+    fn get(&self) -> Option<String>;
+    fn set(&self, text: &str) -> Result<()>;
+}
+
+/// Shells out to whatever copy/paste commands the user configured.
+pub struct ShellClipboardProvider {
+    pub copy_command: String,
+    pub paste_command: String,
+}
+
+impl ClipboardProvider for ShellClipboardProvider {
+    fn get(&self) -> Option<String> {
+        let output = run_shell(&self.paste_command, None).ok()?;
+        Some(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    fn set(&self, text: &str) -> Result<()> {
+        run_shell(&self.copy_command, Some(text))?;
+        Ok(())
+    }
+}
+
+fn run_shell(command: &str, input: Option<&str>) -> Result<Vec<u8>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    if let Some(text) = input {
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("clipboard command `{command}` failed");
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::editor::Action;
+
+/// What a single keystroke resolves to once looked up in a [`KeyMap`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum KeyAction {
+    /// Run one action.
+    Single(Action),
+    /// Run several actions in sequence, e.g. a small macro bound to one key.
+    Multiple(Vec<Action>),
+    /// The key is a prefix; look the next keystroke up in this map (`dd`, `gg`, ...).
+    Nested(HashMap<String, KeyAction>),
+    /// Run the wrapped action the given number of times (at least once), e.g. `5j`.
+    Repeating(u32, Box<KeyAction>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyMap {
+    pub normal: HashMap<String, KeyAction>,
+    pub insert: HashMap<String, KeyAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub keys: KeyMap,
+    /// How long a partial key sequence (e.g. after pressing `g` or a digit) stays
+    /// pending before it is abandoned and the buffer is cleared.
+    pub key_sequence_timeout_ms: u64,
+    /// Maximum number of edits kept on a window's undo stack.
+    pub max_undo_history: usize,
+    /// External copy/paste commands the unnamed register mirrors to, e.g.
+    /// `wl-copy`/`wl-paste`, `pbcopy`/`pbpaste`, or `xclip`. `None` keeps
+    /// registers entirely in-memory.
+    pub clipboard_command: Option<ClipboardCommand>,
+    /// Whether to capture mouse input for click-to-position and wheel
+    /// scrolling. Disable for users who want terminal-native text selection.
+    pub mouse_enabled: bool,
+    /// Lines to scroll per wheel notch.
+    pub mouse_scroll_lines: usize,
+    /// How often the `async-runtime` event loop wakes up between keystrokes
+    /// to run idle-time work (key sequence timeouts, auto-save).
+    pub tick_interval_ms: u64,
+    /// Auto-save a modified buffer after this many idle milliseconds.
+    /// `None` disables auto-save. Only takes effect under `async-runtime`.
+    pub auto_save_interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardCommand {
+    pub copy: String,
+    pub paste: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut normal = HashMap::new();
+        normal.insert("h".into(), KeyAction::Single(Action::MoveLeft));
+        normal.insert("j".into(), KeyAction::Single(Action::MoveDown));
+        normal.insert("k".into(), KeyAction::Single(Action::MoveUp));
+        normal.insert("l".into(), KeyAction::Single(Action::MoveRight));
+        normal.insert("i".into(), KeyAction::Single(Action::InsertMode));
+        normal.insert("x".into(), KeyAction::Single(Action::DeleteUnderCursor));
+        normal.insert("o".into(), KeyAction::Single(Action::InsertLineAfter));
+        normal.insert("O".into(), KeyAction::Single(Action::InsertLineAbove));
+        normal.insert("q".into(), KeyAction::Single(Action::Quit));
+        normal.insert("C-s".into(), KeyAction::Single(Action::Save));
+        normal.insert("u".into(), KeyAction::Single(Action::Undo));
+        normal.insert("C-r".into(), KeyAction::Single(Action::Redo));
+        normal.insert("w".into(), KeyAction::Single(Action::MoveNextWordStart));
+        normal.insert("b".into(), KeyAction::Single(Action::MovePrevWordStart));
+        normal.insert("e".into(), KeyAction::Single(Action::MoveNextWordEnd));
+        normal.insert("0".into(), KeyAction::Single(Action::MoveLineStart));
+        normal.insert("^".into(), KeyAction::Single(Action::MoveFirstNonBlank));
+        normal.insert("$".into(), KeyAction::Single(Action::MoveLineEnd));
+
+        let mut g_nested = HashMap::new();
+        g_nested.insert("g".into(), KeyAction::Single(Action::MoveBufferStart));
+        normal.insert("g".into(), KeyAction::Nested(g_nested));
+
+        let mut d_nested = HashMap::new();
+        d_nested.insert("d".into(), KeyAction::Single(Action::DeleteLine));
+        normal.insert("d".into(), KeyAction::Nested(d_nested));
+
+        let mut y_nested = HashMap::new();
+        y_nested.insert("y".into(), KeyAction::Single(Action::Yank));
+        normal.insert("y".into(), KeyAction::Nested(y_nested));
+
+        normal.insert("p".into(), KeyAction::Single(Action::Put));
+        normal.insert("P".into(), KeyAction::Single(Action::PutBefore));
+
+        Self {
+            keys: KeyMap {
+                normal,
+                insert: HashMap::new(),
+            },
+            key_sequence_timeout_ms: 1000,
+            max_undo_history: 1000,
+            clipboard_command: None,
+            mouse_enabled: true,
+            mouse_scroll_lines: 3,
+            tick_interval_ms: 250,
+            auto_save_interval_ms: None,
+        }
+    }
+}
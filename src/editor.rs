@@ -1,15 +1,27 @@
+use std::collections::HashMap;
 use std::io::{stdout, Stdout, Write};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use crossterm::{
     cursor::{self, SetCursorStyle},
-    event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
     style,
     terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand, QueueableCommand,
 };
+#[cfg(not(feature = "async-runtime"))]
+use crossterm::event::{poll, read};
+#[cfg(feature = "async-runtime")]
+use crossterm::event::EventStream;
+#[cfg(feature = "async-runtime")]
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
+use crate::clipboard::{ClipboardProvider, ShellClipboardProvider};
 use crate::window::Window;
 use crate::config::{Config, KeyAction};
 
@@ -19,11 +31,38 @@ pub enum Mode {
     Insert,
 }
 
+/// The contents of one yank/delete register, along with whether it should be
+/// put back as whole lines or inserted in place at the cursor.
+#[derive(Debug, Clone, Default)]
+pub struct Register {
+    pub text: String,
+    pub linewise: bool,
+}
+
 pub struct Editor {
     out: Stdout,
     config: Config,
     current_buffer: Window,
-    alt_buffers: Vec<Window>,
+    /// Nested key map we're currently inside, e.g. after pressing `d` while
+    /// waiting for the second `d` of `dd`.
+    pending_nested: Option<HashMap<String, KeyAction>>,
+    /// Digits typed so far for a count prefix, e.g. the `5` of `5j`.
+    pending_count: Option<u32>,
+    /// True right after `"`, waiting for the register letter that follows it.
+    selecting_register: bool,
+    /// The register selected by a preceding `"a`, consumed by the next yank/
+    /// delete/put.
+    pending_register: Option<char>,
+    /// The raw keys making up the in-progress sequence, for display.
+    pending_keys: String,
+    /// When the in-progress sequence was last extended, to time it out.
+    pending_since: Option<Instant>,
+    /// Named registers `a`-`z`.
+    registers: HashMap<char, Register>,
+    /// The unnamed register every yank/delete also lands in.
+    unnamed_register: Register,
+    /// Mirrors the unnamed register to the OS clipboard, if configured.
+    clipboard: Option<Box<dyn ClipboardProvider>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -35,21 +74,121 @@ pub enum Action {
     MoveRight,
     InsertMode,
     DeleteUnderCursor,
+    DeleteLine,
+    MoveBufferStart,
+    MoveNextWordStart,
+    MovePrevWordStart,
+    MoveNextWordEnd,
+    MoveLineStart,
+    MoveFirstNonBlank,
+    MoveLineEnd,
     NormalMode,
     Save,
     InsertLineAfter,
     InsertLineAbove,
+    Undo,
+    Redo,
+    Yank,
+    Put,
+    PutBefore,
+}
+
+/// The three character classes `w`/`b`/`e` use to find word boundaries:
+/// alphanumeric-or-underscore runs, punctuation runs, and whitespace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Word,
+    Punct,
+    Space,
+}
+
+fn classify(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
 }
 
 impl Editor {
     fn move_cursor(&mut self, x: u16, y: u16) {
         self.current_buffer.cursor.x = x;
         self.current_buffer.cursor.y = y;
-        self.out.queue(cursor::MoveTo(x, y)).unwrap();
+    }
+
+    /// Clamp the cursor to the buffer and scroll the viewport so it stays
+    /// visible. Call this after anything that may have moved the cursor or
+    /// changed the buffer.
+    fn after_cursor_move(&mut self) {
+        self.current_buffer.clamp_cursor();
+        self.adjust_scroll();
+    }
+
+    fn adjust_scroll(&mut self) {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let text_rows = rows.saturating_sub(1) as usize;
+        let cols = cols as usize;
+
+        let cursor_y = self.current_buffer.cursor.y as usize;
+        let cursor_x = self.current_buffer.cursor.x as usize;
+        let mut scrolled = false;
+
+        if cursor_y < self.current_buffer.scroll_top {
+            self.current_buffer.scroll_top = cursor_y;
+            scrolled = true;
+        } else if text_rows > 0 && cursor_y >= self.current_buffer.scroll_top + text_rows {
+            self.current_buffer.scroll_top = cursor_y + 1 - text_rows;
+            scrolled = true;
+        }
+
+        if cursor_x < self.current_buffer.scroll_left {
+            self.current_buffer.scroll_left = cursor_x;
+            scrolled = true;
+        } else if cols > 0 && cursor_x >= self.current_buffer.scroll_left + cols {
+            self.current_buffer.scroll_left = cursor_x + 1 - cols;
+            scrolled = true;
+        }
+
+        if scrolled {
+            self.current_buffer.render_buffer = true;
+        }
+    }
+
+    fn render_cursor(&mut self) {
+        let x = self.current_buffer.cursor.x as usize - self.current_buffer.scroll_left;
+        let y = self.current_buffer.cursor.y as usize - self.current_buffer.scroll_top;
+        self.out.queue(cursor::MoveTo(x as u16, y as u16)).unwrap();
+    }
+
+    fn render_status_line(&mut self, row: u16, cols: u16) {
+        let mode = match self.current_buffer.mode {
+            Mode::Normal => "NORMAL",
+            Mode::Insert => "INSERT",
+        };
+        let modified = if self.current_buffer.modified { " [+]" } else { "" };
+        let mut status = format!(
+            "{} {}{}  {}:{}",
+            mode,
+            self.current_buffer.path,
+            modified,
+            self.current_buffer.cursor.y + 1,
+            self.current_buffer.cursor.x + 1,
+        );
+        if !self.pending_keys.is_empty() {
+            status.push_str(&format!("  [{}]", self.pending_keys));
+        }
+        let status: String = status.chars().take(cols as usize).collect();
+
+        self.out.queue(cursor::MoveTo(0, row)).unwrap();
+        self.out.queue(terminal::Clear(ClearType::CurrentLine)).unwrap();
+        self.out.queue(style::Print(status)).unwrap();
     }
 
     fn enter_insert_mode(&mut self) {
         self.current_buffer.mode = Mode::Insert;
+        self.current_buffer.fresh_insert = true;
         self.out.queue(SetCursorStyle::BlinkingBar).unwrap();
     }
 
@@ -64,12 +203,68 @@ impl Editor {
 
     fn enter_alt_screen(&mut self) {
         self.out.execute(EnterAlternateScreen).unwrap();
+        if self.config.mouse_enabled {
+            self.out.execute(EnableMouseCapture).unwrap();
+        }
     }
 
     fn leave_alt_screen(&mut self) {
+        if self.config.mouse_enabled {
+            self.out.execute(DisableMouseCapture).unwrap();
+        }
         self.out.execute(LeaveAlternateScreen).unwrap();
     }
 
+    fn handle_mouse_event(&mut self, event: MouseEvent) {
+        let (_, rows) = terminal::size().unwrap_or((80, 24));
+        let text_rows = rows.saturating_sub(1);
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if event.row >= text_rows {
+                    return;
+                }
+
+                let y = (self.current_buffer.scroll_top + event.row as usize)
+                    .min(self.current_buffer.buffer.len().saturating_sub(1));
+                let line_len = self.current_buffer.buffer[y].len();
+                let x = (self.current_buffer.scroll_left + event.column as usize).min(line_len);
+
+                self.move_cursor(x as u16, y as u16);
+                self.after_cursor_move();
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll_viewport(-(self.config.mouse_scroll_lines as isize))
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_viewport(self.config.mouse_scroll_lines as isize)
+            }
+            _ => (),
+        }
+    }
+
+    /// Move the viewport without moving the logical cursor, e.g. for wheel
+    /// scrolling. If the scroll pushes the cursor's row out of view, the
+    /// cursor is pulled back onto the nearest visible row so it never ends
+    /// up above `scroll_top` (which `render_cursor` can't represent).
+    fn scroll_viewport(&mut self, delta: isize) {
+        let (_, rows) = terminal::size().unwrap_or((80, 24));
+        let text_rows = rows.saturating_sub(1) as usize;
+
+        let max_top = self.current_buffer.buffer.len().saturating_sub(1) as isize;
+        let top = self.current_buffer.scroll_top as isize + delta;
+        self.current_buffer.scroll_top = top.clamp(0, max_top) as usize;
+
+        let min_y = self.current_buffer.scroll_top;
+        let max_y = (self.current_buffer.scroll_top + text_rows.saturating_sub(1))
+            .min(self.current_buffer.buffer.len().saturating_sub(1));
+        let y = (self.current_buffer.cursor.y as usize).clamp(min_y, max_y);
+        self.move_cursor(self.current_buffer.cursor.x, y as u16);
+        self.current_buffer.clamp_cursor();
+
+        self.current_buffer.render_buffer = true;
+    }
+
     fn raw(&mut self) {
         terminal::enable_raw_mode().unwrap();
     }
@@ -84,12 +279,18 @@ impl Editor {
 
     pub fn handle_key_event(&mut self, action: Option<KeyAction>) {
         match action {
-            Some(action) => match action {
-                KeyAction::Single(a) => self.handle_single_action(a),
-                KeyAction::Multiple(_) => (),
-                KeyAction::Nested(_) => (),
-                KeyAction::Repeating(_, _) => (),
-            },
+            Some(KeyAction::Single(a)) => self.handle_single_action(a),
+            Some(KeyAction::Multiple(actions)) => {
+                for a in actions {
+                    self.handle_single_action(a);
+                }
+            }
+            Some(KeyAction::Nested(map)) => self.dispatch_resolved(KeyAction::Nested(map)),
+            Some(KeyAction::Repeating(n, inner)) => {
+                for _ in 0..n.max(1) {
+                    self.handle_key_event(Some((*inner).clone()));
+                }
+            }
             None => (),
         }
     }
@@ -97,74 +298,204 @@ impl Editor {
     pub fn new(config: Config, path: String) -> anyhow::Result<Self> {
         let out: Stdout = stdout();
 
-        match Window::new(path.clone()) {
-            Err(e) => return Err(e),
+        let clipboard: Option<Box<dyn ClipboardProvider>> =
+            config.clipboard_command.as_ref().map(|cmd| {
+                Box::new(ShellClipboardProvider {
+                    copy_command: cmd.copy.clone(),
+                    paste_command: cmd.paste.clone(),
+                }) as Box<dyn ClipboardProvider>
+            });
+
+        match Window::new(path.clone(), config.max_undo_history) {
+            Err(e) => Err(e),
             Ok(w) => Ok(Self {
                 out,
                 config,
                 current_buffer: w,
-                alt_buffers: Vec::new(),
+                pending_nested: None,
+                pending_count: None,
+                selecting_register: false,
+                pending_register: None,
+                pending_keys: String::new(),
+                pending_since: None,
+                registers: HashMap::new(),
+                unnamed_register: Register::default(),
+                clipboard,
             }),
         }
     }
 
+    /// Store a yank/delete in the targeted register (if `"a` preceded it)
+    /// and always in the unnamed register, mirroring it to the OS clipboard
+    /// when one is configured.
+    fn write_register(&mut self, text: String, linewise: bool) {
+        let register = Register { text, linewise };
+        if let Some(name) = self.pending_register {
+            self.registers.insert(name, register.clone());
+        }
+        if let Some(clipboard) = &self.clipboard {
+            let _ = clipboard.set(&register.text);
+        }
+        self.unnamed_register = register;
+    }
+
+    /// Read the targeted register (if `"a` preceded the put), or the
+    /// unnamed register, preferring a live OS clipboard read when one is
+    /// configured.
+    fn read_register(&self) -> Register {
+        match self.pending_register {
+            Some(name) => self.registers.get(&name).cloned().unwrap_or_default(),
+            None => match self.clipboard.as_ref().and_then(|c| c.get()) {
+                Some(text) => Register {
+                    text,
+                    linewise: self.unnamed_register.linewise,
+                },
+                None => self.unnamed_register.clone(),
+            },
+        }
+    }
+
+    /// A resolved key either starts a new nested sequence (stash it and wait
+    /// for the next key) or is terminal, in which case it runs now, wrapped
+    /// in `Repeating` if a count prefix was pending.
+    fn dispatch_resolved(&mut self, action: KeyAction) {
+        if let KeyAction::Nested(map) = action {
+            self.pending_nested = Some(map);
+            self.pending_since = Some(Instant::now());
+            return;
+        }
+
+        let count = self.pending_count.take();
+        self.pending_keys.clear();
+        self.pending_since = None;
+
+        match count {
+            Some(n) => self.handle_key_event(Some(KeyAction::Repeating(n, Box::new(action)))),
+            None => self.handle_key_event(Some(action)),
+        }
+
+        self.pending_register = None;
+    }
+
+    /// Abort whatever partial `dd`/`gg`/count/register sequence is in
+    /// progress, e.g. because the next key didn't match or the sequence
+    /// timed out.
+    fn abort_pending_sequence(&mut self) {
+        self.pending_nested = None;
+        self.pending_count = None;
+        self.selecting_register = false;
+        self.pending_register = None;
+        self.pending_keys.clear();
+        self.pending_since = None;
+    }
+
     fn handle_normal_event(&mut self, event: Event) {
-        match event {
-            Event::Key(KeyEvent {
-                code, modifiers, ..
-            }) => match code {
+        if let Event::Key(KeyEvent {
+            code, modifiers, ..
+        }) = event
+        {
+            match code {
+                KeyCode::Esc => self.abort_pending_sequence(),
                 KeyCode::Char(c) => {
-                    let action = self.config.keys..get(&c.to_string()).cloned();
-                    
+                    if self.selecting_register {
+                        self.selecting_register = false;
+                        if c.is_ascii_alphabetic() {
+                            self.pending_register = Some(c.to_ascii_lowercase());
+                            self.pending_keys.push(c);
+                            self.pending_since = Some(Instant::now());
+                        } else {
+                            self.abort_pending_sequence();
+                        }
+                        return;
+                    }
+
+                    if self.pending_nested.is_none() && self.pending_register.is_none() && c == '"'
+                    {
+                        self.selecting_register = true;
+                        self.pending_keys.push(c);
+                        self.pending_since = Some(Instant::now());
+                        return;
+                    }
+
+                    if self.pending_nested.is_none()
+                        && c.is_ascii_digit()
+                        && (c != '0' || self.pending_count.is_some())
+                    {
+                        let digit = c.to_digit(10).unwrap();
+                        self.pending_count = Some(self.pending_count.unwrap_or(0) * 10 + digit);
+                        self.pending_keys.push(c);
+                        self.pending_since = Some(Instant::now());
+                        return;
+                    }
+
                     let modifier = match modifiers {
                         KeyModifiers::SHIFT => "S-",
                         KeyModifiers::CONTROL => "C-",
                         _ => "",
                     };
+                    let key = format!("{modifier}{c}");
 
-                    let normal = self.config.keys.normal.clone();
+                    let map = self
+                        .pending_nested
+                        .take()
+                        .unwrap_or_else(|| self.config.keys.normal.clone());
+                    let action = map.get(&key).cloned();
+                    self.pending_keys.push_str(&key);
 
-                    let action = normal.get(&format!("{modifier}{c}")).cloned();
                     match action {
-                        Some(_) => self.handle_key_event(action.clone()),
-                        None => (),
+                        Some(action) => self.dispatch_resolved(action),
+                        None => self.abort_pending_sequence(),
                     }
                 }
                 _ => (),
-            },
-            _ => (),
+            }
         }
     }
 
     fn handle_insert_event(&mut self, event: Event) {
-        match event {
-            Event::Key(KeyEvent {
-                code, ..
-            }) => match code {
+        if let Event::Key(KeyEvent { code, .. }) = event {
+            match code {
                 KeyCode::Char(c) => {
                     self.current_buffer.insert(c.to_string());
                 }
                 KeyCode::Esc => {
                     self.enter_normal_mode();
                 }
-                _ => ()
-            },
-            _ => (),
+                _ => (),
+            }
         }
+        self.after_cursor_move();
     }
 
     pub fn refresh_screen(&mut self) {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let text_rows = rows.saturating_sub(1);
+
         if self.current_buffer.render_buffer {
             self.clear();
-            for (i, line) in self.current_buffer.buffer.iter().enumerate() {
-                self.out.queue(cursor::MoveTo(0, i as u16)).unwrap();
-                self.out.queue(style::Print(format!("{}\r", line))).unwrap();
+            let top = self.current_buffer.scroll_top;
+            let left = self.current_buffer.scroll_left;
+
+            for (row, line) in self
+                .current_buffer
+                .buffer
+                .iter()
+                .skip(top)
+                .take(text_rows as usize)
+                .enumerate()
+            {
+                let visible: String = line.chars().skip(left).take(cols as usize).collect();
+                self.out.queue(cursor::MoveTo(0, row as u16)).unwrap();
+                self.out.queue(style::Print(format!("{}\r", visible))).unwrap();
             }
-            self.move_cursor(self.current_buffer.cursor.x, self.current_buffer.cursor.y);
             self.current_buffer.render_buffer = false;
         }
+
+        self.render_status_line(text_rows, cols);
+        self.render_cursor();
     }
 
+    #[cfg(not(feature = "async-runtime"))]
     pub fn run(&mut self) -> Result<()> {
         self.clear();
         self.enter_alt_screen();
@@ -174,13 +505,246 @@ impl Editor {
             self.refresh_screen();
             self.flush();
 
-            let ev = read()?;
+            let ev = match self.pending_since {
+                Some(since) => {
+                    let timeout = Duration::from_millis(self.config.key_sequence_timeout_ms)
+                        .saturating_sub(since.elapsed());
+                    if poll(timeout)? {
+                        Some(read()?)
+                    } else {
+                        self.abort_pending_sequence();
+                        None
+                    }
+                }
+                None => Some(read()?),
+            };
 
-            match self.current_buffer.mode {
+            let Some(ev) = ev else { continue };
+            self.dispatch_terminal_event(ev);
+        }
+    }
+
+    /// Bridges into the `async-runtime` event loop. Kept as a plain,
+    /// blocking entry point so `main` doesn't need to know which feature is
+    /// compiled in.
+    #[cfg(feature = "async-runtime")]
+    pub fn run(&mut self) -> Result<()> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        rt.block_on(self.run_async())
+    }
+
+    /// Event-driven replacement for the blocking [`read`] loop. Terminal
+    /// events and a periodic tick share one `select!`, so idle-time work
+    /// (key sequence timeouts, auto-save) happens without busy-waiting on
+    /// keystrokes.
+    #[cfg(feature = "async-runtime")]
+    async fn run_async(&mut self) -> Result<()> {
+        self.clear();
+        self.enter_alt_screen();
+        self.raw();
+
+        let mut events = EventStream::new();
+        let mut ticker = tokio::time::interval(Duration::from_millis(self.config.tick_interval_ms));
+
+        loop {
+            self.refresh_screen();
+            self.flush();
+
+            tokio::select! {
+                ev = events.next() => {
+                    let Some(ev) = ev else { break };
+                    self.dispatch_terminal_event(ev?);
+                }
+                _ = ticker.tick() => self.on_tick(),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs on every `async-runtime` tick: times out an in-progress key
+    /// sequence and, if configured, auto-saves a buffer that has sat
+    /// modified for `auto_save_interval_ms` since its last edit (not its
+    /// last save), so continuous typing doesn't trigger a save every tick.
+    #[cfg(feature = "async-runtime")]
+    fn on_tick(&mut self) {
+        if let Some(since) = self.pending_since {
+            if since.elapsed() >= Duration::from_millis(self.config.key_sequence_timeout_ms) {
+                self.abort_pending_sequence();
+            }
+        }
+
+        if let Some(interval_ms) = self.config.auto_save_interval_ms {
+            let idle = self.current_buffer.last_edit.elapsed() >= Duration::from_millis(interval_ms);
+            if self.current_buffer.modified && idle {
+                let _ = self.current_buffer.save();
+            }
+        }
+    }
+
+    fn dispatch_terminal_event(&mut self, ev: Event) {
+        match ev {
+            Event::Resize(_, _) => {
+                self.current_buffer.render_buffer = true;
+                self.after_cursor_move();
+            }
+            Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event),
+            _ => match self.current_buffer.mode {
                 Mode::Normal => self.handle_normal_event(ev),
                 Mode::Insert => self.handle_insert_event(ev),
+            },
+        }
+    }
+
+    /// The character starting at byte offset `x` of line `y`. `x` is a byte
+    /// offset (matching `cursor.x`/`line_len` elsewhere), so this decodes
+    /// UTF-8 from that point rather than reinterpreting a single byte as a
+    /// scalar value; `None` if `y` is out of range or `x` doesn't land on a
+    /// char boundary.
+    fn char_at(&self, y: usize, x: usize) -> Option<char> {
+        self.current_buffer
+            .buffer
+            .get(y)
+            .and_then(|line| line.get(x..))
+            .and_then(|rest| rest.chars().next())
+    }
+
+    fn line_len(&self, y: usize) -> usize {
+        self.current_buffer.buffer.get(y).map_or(0, String::len)
+    }
+
+    fn advance(&self, (y, x): (usize, usize)) -> Option<(usize, usize)> {
+        if x + 1 < self.line_len(y) {
+            Some((y, x + 1))
+        } else if y + 1 < self.current_buffer.buffer.len() {
+            Some((y + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    fn retreat(&self, (y, x): (usize, usize)) -> Option<(usize, usize)> {
+        if x > 0 {
+            Some((y, x - 1))
+        } else if y > 0 {
+            let py = y - 1;
+            Some((py, self.line_len(py).saturating_sub(1)))
+        } else {
+            None
+        }
+    }
+
+    /// Advance `pos` while it sits on a character belonging to `class`,
+    /// treating a position past the end of a (possibly empty) line as
+    /// whitespace. Stops at the first non-matching position or the end of
+    /// the buffer. If `same_line` is set, also stops at the end of the
+    /// starting line rather than continuing onto the next one, even if the
+    /// next line's first character happens to share `class`.
+    fn skip_while_class(&self, mut pos: (usize, usize), class: CharClass, same_line: bool) -> (usize, usize) {
+        let start_line = pos.0;
+        loop {
+            let matches = match self.char_at(pos.0, pos.1) {
+                Some(c) => classify(c) == class,
+                None => class == CharClass::Space,
+            };
+            if !matches {
+                return pos;
+            }
+            match self.advance(pos) {
+                Some(next) if same_line && next.0 != start_line => {
+                    return (start_line, self.line_len(start_line));
+                }
+                Some(next) => pos = next,
+                None => return pos,
+            }
+        }
+    }
+
+    fn move_next_word_start(&mut self) {
+        let mut pos = (
+            self.current_buffer.cursor.y as usize,
+            self.current_buffer.cursor.x as usize,
+        );
+        let class = self.char_at(pos.0, pos.1).map_or(CharClass::Space, classify);
+        if class != CharClass::Space {
+            pos = self.skip_while_class(pos, class, true);
+        }
+        pos = self.skip_while_class(pos, CharClass::Space, false);
+        self.move_cursor(pos.1 as u16, pos.0 as u16);
+    }
+
+    fn move_prev_word_start(&mut self) {
+        let start = (
+            self.current_buffer.cursor.y as usize,
+            self.current_buffer.cursor.x as usize,
+        );
+        let Some(mut pos) = self.retreat(start) else {
+            return;
+        };
+
+        loop {
+            let is_space = self
+                .char_at(pos.0, pos.1)
+                .is_none_or(|c| classify(c) == CharClass::Space);
+            if !is_space {
+                break;
+            }
+            match self.retreat(pos) {
+                Some(prev) => pos = prev,
+                None => {
+                    self.move_cursor(pos.1 as u16, pos.0 as u16);
+                    return;
+                }
             }
         }
+
+        let class = classify(self.char_at(pos.0, pos.1).unwrap());
+        while let Some(prev) = self.retreat(pos) {
+            match self.char_at(prev.0, prev.1) {
+                Some(c) if classify(c) == class => pos = prev,
+                _ => break,
+            }
+        }
+
+        self.move_cursor(pos.1 as u16, pos.0 as u16);
+    }
+
+    fn move_next_word_end(&mut self) {
+        let start = (
+            self.current_buffer.cursor.y as usize,
+            self.current_buffer.cursor.x as usize,
+        );
+        let Some(mut pos) = self.advance(start) else {
+            return;
+        };
+
+        loop {
+            let is_space = self
+                .char_at(pos.0, pos.1)
+                .is_none_or(|c| classify(c) == CharClass::Space);
+            if !is_space {
+                break;
+            }
+            match self.advance(pos) {
+                Some(next) => pos = next,
+                None => {
+                    self.move_cursor(pos.1 as u16, pos.0 as u16);
+                    return;
+                }
+            }
+        }
+
+        let class = classify(self.char_at(pos.0, pos.1).unwrap());
+        while let Some(next) = self.advance(pos) {
+            match self.char_at(next.0, next.1) {
+                Some(c) if classify(c) == class => pos = next,
+                _ => break,
+            }
+        }
+
+        self.move_cursor(pos.1 as u16, pos.0 as u16);
     }
 
     fn handle_single_action(&mut self, a: Action) {
@@ -190,36 +754,18 @@ impl Editor {
                 self.disable_raw();
                 std::process::exit(0);
             }
-            Action::MoveUp => {
-                if self.current_buffer.cursor.y > 0 {
-                    self.move_cursor(
-                        self.current_buffer.cursor.x,
-                        self.current_buffer.cursor.y - 1,
-                    );
-                } else {
-                    self.move_cursor(
-                        self.current_buffer.cursor.x,
-                        self.current_buffer.cursor.y,
-                    );
-                }
-
-            }
+            Action::MoveUp => self.move_cursor(
+                self.current_buffer.cursor.x,
+                self.current_buffer.cursor.y.saturating_sub(1),
+            ),
             Action::MoveDown => self.move_cursor(
                 self.current_buffer.cursor.x,
                 self.current_buffer.cursor.y + 1,
             ),
-            Action::MoveLeft => 
-                if self.current_buffer.cursor.x > 0 {
-                    self.move_cursor(
-                        self.current_buffer.cursor.x - 1,
-                        self.current_buffer.cursor.y,
-                    );
-                } else {
-                    self.move_cursor(
-                        self.current_buffer.cursor.x,
-                        self.current_buffer.cursor.y,
-                    );
-                }
+            Action::MoveLeft => self.move_cursor(
+                self.current_buffer.cursor.x.saturating_sub(1),
+                self.current_buffer.cursor.y,
+            ),
             Action::MoveRight => self.move_cursor(
                 self.current_buffer.cursor.x + 1,
                 self.current_buffer.cursor.y,
@@ -228,11 +774,438 @@ impl Editor {
             Action::NormalMode => self.enter_normal_mode(),
             Action::InsertLineAfter => self.current_buffer.insert_line_below(),
             Action::InsertLineAbove => self.current_buffer.insert_line_above(),
-            Action::DeleteUnderCursor => self.current_buffer.delete_under_cursor(), 
+            Action::DeleteUnderCursor => self.current_buffer.delete_under_cursor(),
+            Action::DeleteLine => {
+                let y = self.current_buffer.cursor.y as usize;
+                let line = self.current_buffer.buffer[y].clone();
+                self.write_register(format!("{line}\n"), true);
+                self.current_buffer.delete_line();
+            }
+            Action::MoveBufferStart => self.move_cursor(0, 0),
+            Action::MoveNextWordStart => self.move_next_word_start(),
+            Action::MovePrevWordStart => self.move_prev_word_start(),
+            Action::MoveNextWordEnd => self.move_next_word_end(),
+            Action::MoveLineStart => self.move_cursor(0, self.current_buffer.cursor.y),
+            Action::MoveFirstNonBlank => {
+                let y = self.current_buffer.cursor.y;
+                let x = self.current_buffer.buffer[y as usize]
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(0) as u16;
+                self.move_cursor(x, y);
+            }
+            Action::MoveLineEnd => {
+                let y = self.current_buffer.cursor.y;
+                let x = self.current_buffer.buffer[y as usize].len().saturating_sub(1) as u16;
+                self.move_cursor(x, y);
+            }
+            Action::Undo => self.current_buffer.undo(),
+            Action::Redo => self.current_buffer.redo(),
+            Action::Yank => {
+                let y = self.current_buffer.cursor.y as usize;
+                let line = self.current_buffer.buffer[y].clone();
+                self.write_register(format!("{line}\n"), true);
+            }
+            Action::Put => {
+                let register = self.read_register();
+                if register.linewise {
+                    let y = self.current_buffer.cursor.y as usize + 1;
+                    self.current_buffer.put_lines(y, &register.text);
+                } else if !register.text.is_empty() {
+                    self.current_buffer.cursor.x += 1;
+                    self.current_buffer.insert(register.text);
+                }
+            }
+            Action::PutBefore => {
+                let register = self.read_register();
+                if register.linewise {
+                    let y = self.current_buffer.cursor.y as usize;
+                    self.current_buffer.put_lines(y, &register.text);
+                } else if !register.text.is_empty() {
+                    self.current_buffer.insert(register.text);
+                }
+            }
             Action::Save => match self.current_buffer.save().map_err(|e| e.to_string()) {
                 Ok(_) => (),
                 Err(e) => eprintln!("{}", e),
-            }, 
+            },
         }
+        self.after_cursor_move();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn editor(line: &str) -> Editor {
+        let mut editor = Editor::new(Config::default(), "pep-test-nonexistent-file.tmp".to_string())
+            .unwrap();
+        editor.current_buffer.buffer = vec![line.to_string()];
+        editor
+    }
+
+    fn editor_lines(lines: &[&str]) -> Editor {
+        let mut editor = Editor::new(Config::default(), "pep-test-nonexistent-file.tmp".to_string())
+            .unwrap();
+        editor.current_buffer.buffer = lines.iter().map(|l| l.to_string()).collect();
+        editor
+    }
+
+    #[test]
+    fn classify_splits_word_punct_and_space() {
+        assert_eq!(classify('a'), CharClass::Word);
+        assert_eq!(classify('_'), CharClass::Word);
+        assert_eq!(classify('.'), CharClass::Punct);
+        assert_eq!(classify(' '), CharClass::Space);
+    }
+
+    #[test]
+    fn w_stops_at_the_start_of_the_next_word() {
+        let mut e = editor("foo bar");
+        e.move_next_word_start();
+        assert_eq!(e.current_buffer.cursor.x, 4);
+    }
+
+    #[test]
+    fn w_treats_a_punctuation_run_as_its_own_word() {
+        let mut e = editor("foo.bar baz");
+        e.move_next_word_start();
+        assert_eq!(e.current_buffer.cursor.x, 3);
+    }
+
+    #[test]
+    fn w_on_the_last_word_of_a_line_moves_to_the_start_of_the_next_line_without_swallowing_it() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.move_next_word_start();
+        assert_eq!((e.current_buffer.cursor.y, e.current_buffer.cursor.x), (1, 0));
+    }
+
+    #[test]
+    fn w_on_the_last_word_of_the_buffer_stays_on_the_last_char() {
+        let mut e = editor("foo");
+        e.move_next_word_start();
+        assert_eq!(e.current_buffer.cursor.x, 2);
+    }
+
+    #[test]
+    fn b_moves_to_the_start_of_the_previous_word() {
+        let mut e = editor("foo bar");
+        e.current_buffer.cursor.x = 4;
+        e.move_prev_word_start();
+        assert_eq!(e.current_buffer.cursor.x, 0);
+    }
+
+    #[test]
+    fn e_moves_to_the_end_of_the_current_then_next_word() {
+        let mut e = editor("foo bar");
+        e.move_next_word_end();
+        assert_eq!(e.current_buffer.cursor.x, 2);
+        e.move_next_word_end();
+        assert_eq!(e.current_buffer.cursor.x, 6);
+    }
+
+    #[test]
+    fn word_motions_on_an_empty_line_do_not_panic() {
+        let mut e = editor("");
+        e.move_next_word_start();
+        e.move_prev_word_start();
+        e.move_next_word_end();
+        assert_eq!(e.current_buffer.cursor.x, 0);
+    }
+
+    #[test]
+    fn line_anchors_find_start_first_non_blank_and_end() {
+        let mut e = editor("  foo");
+        e.handle_single_action(Action::MoveFirstNonBlank);
+        assert_eq!(e.current_buffer.cursor.x, 2);
+        e.handle_single_action(Action::MoveLineStart);
+        assert_eq!(e.current_buffer.cursor.x, 0);
+        e.handle_single_action(Action::MoveLineEnd);
+        assert_eq!(e.current_buffer.cursor.x, 4);
+    }
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn a_count_prefix_repeats_the_following_motion() {
+        let mut e = editor_lines(&["a", "b", "c", "d", "e", "f"]);
+        e.handle_normal_event(key('5'));
+        e.handle_normal_event(key('j'));
+        assert_eq!(e.current_buffer.cursor.y, 5);
+    }
+
+    #[test]
+    fn a_leading_zero_is_the_line_start_motion_not_a_count_digit() {
+        let mut e = editor("foo");
+        e.current_buffer.cursor.x = 2;
+        e.handle_normal_event(key('0'));
+        assert_eq!(e.current_buffer.cursor.x, 0);
+    }
+
+    #[test]
+    fn a_zero_after_a_nonzero_digit_extends_the_count() {
+        let lines: Vec<String> = (0..11).map(|_| "x".to_string()).collect();
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut e = editor_lines(&refs);
+        e.handle_normal_event(key('1'));
+        e.handle_normal_event(key('0'));
+        e.handle_normal_event(key('j'));
+        assert_eq!(e.current_buffer.cursor.y, 10);
+    }
+
+    #[test]
+    fn dd_is_a_nested_sequence_that_only_fires_on_the_second_d() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.handle_normal_event(key('d'));
+        assert_eq!(e.current_buffer.buffer, vec!["foo".to_string(), "bar".to_string()]);
+        e.handle_normal_event(key('d'));
+        assert_eq!(e.current_buffer.buffer, vec!["bar".to_string()]);
+    }
+
+    #[test]
+    fn an_unmatched_second_key_aborts_the_pending_nested_sequence() {
+        let mut e = editor("foo");
+        e.handle_normal_event(key('d'));
+        assert!(e.pending_nested.is_some());
+        e.handle_normal_event(key('z'));
+        assert!(e.pending_nested.is_none());
+        assert!(e.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn esc_aborts_a_pending_count_and_nested_sequence() {
+        let mut e = editor("foo");
+        e.handle_normal_event(key('5'));
+        e.handle_normal_event(key('d'));
+        e.handle_normal_event(Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(e.pending_count.is_none());
+        assert!(e.pending_nested.is_none());
+        assert!(e.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn a_named_register_prefix_targets_yank_and_put() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.handle_normal_event(key('"'));
+        e.handle_normal_event(key('a'));
+        e.handle_normal_event(key('y'));
+        e.handle_normal_event(key('y'));
+        assert_eq!(e.registers.get(&'a').unwrap().text, "foo\n");
+
+        e.current_buffer.buffer = vec!["bar".to_string()];
+        e.current_buffer.cursor.y = 0;
+        e.handle_normal_event(key('"'));
+        e.handle_normal_event(key('a'));
+        e.handle_normal_event(key('p'));
+        assert_eq!(e.current_buffer.buffer, vec!["bar".to_string(), "foo".to_string()]);
+    }
+
+    struct FakeClipboard {
+        text: std::cell::RefCell<String>,
+    }
+
+    impl ClipboardProvider for FakeClipboard {
+        fn get(&self) -> Option<String> {
+            Some(self.text.borrow().clone())
+        }
+
+        fn set(&self, text: &str) -> anyhow::Result<()> {
+            *self.text.borrow_mut() = text.to_string();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn yank_and_put_round_trip_a_whole_line_linewise() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.handle_single_action(Action::Yank);
+        e.handle_single_action(Action::Put);
+        assert_eq!(e.current_buffer.buffer, vec!["foo".to_string(), "foo".to_string(), "bar".to_string()]);
+    }
+
+    #[test]
+    fn put_inserts_charwise_text_in_place_rather_than_as_a_new_line() {
+        let mut e = editor("abc");
+        e.unnamed_register = Register {
+            text: "X".to_string(),
+            linewise: false,
+        };
+        e.handle_single_action(Action::Put);
+        assert_eq!(e.current_buffer.buffer, vec!["aXbc".to_string()]);
+    }
+
+    #[test]
+    fn a_named_register_is_independent_of_the_unnamed_register() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.pending_register = Some('a');
+        e.handle_single_action(Action::Yank);
+        assert_eq!(e.registers.get(&'a').unwrap().text, "foo\n");
+
+        e.current_buffer.cursor.y = 1;
+        e.pending_register = None;
+        e.handle_single_action(Action::Yank);
+        assert_eq!(e.unnamed_register.text, "bar\n");
+        assert_eq!(e.registers.get(&'a').unwrap().text, "foo\n");
+    }
+
+    #[test]
+    fn read_register_keeps_the_stored_linewise_flag_when_substituting_clipboard_text() {
+        let mut e = editor_lines(&["foo", "bar"]);
+        e.handle_single_action(Action::Yank);
+        assert!(e.unnamed_register.linewise);
+
+        e.clipboard = Some(Box::new(FakeClipboard {
+            text: std::cell::RefCell::new("clip\n".to_string()),
+        }));
+        let register = e.read_register();
+        assert!(register.linewise);
+        assert_eq!(register.text, "clip\n");
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn a_left_click_moves_the_cursor_to_the_clicked_cell() {
+        let mut e = editor_lines(&["hello world", "second line"]);
+        e.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 4, 1));
+        assert_eq!((e.current_buffer.cursor.x, e.current_buffer.cursor.y), (4, 1));
+    }
+
+    #[test]
+    fn a_left_click_past_the_end_of_a_line_clamps_to_the_last_char() {
+        let mut e = editor_lines(&["hi", "second line"]);
+        e.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 50, 0));
+        assert_eq!(e.current_buffer.cursor.x as usize, e.current_buffer.buffer[0].len() - 1);
+    }
+
+    #[test]
+    fn a_left_click_accounts_for_the_current_scroll_offset() {
+        let mut e = editor_with_rows(100);
+        e.current_buffer.scroll_top = 10;
+        e.current_buffer.scroll_left = 2;
+        e.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 3, 5));
+        assert_eq!((e.current_buffer.cursor.x, e.current_buffer.cursor.y), (5, 15));
+    }
+
+    #[test]
+    fn wheel_scroll_moves_the_viewport_without_moving_a_still_visible_cursor() {
+        let mut e = editor_with_rows(100);
+        e.current_buffer.cursor.y = 5;
+        e.handle_mouse_event(mouse(MouseEventKind::ScrollDown, 0, 0));
+        assert!(e.current_buffer.scroll_top > 0);
+        assert_eq!(e.current_buffer.cursor.y, 5);
+    }
+
+    #[test]
+    fn wheel_scroll_pulls_the_cursor_back_into_view_instead_of_leaving_it_above_the_viewport() {
+        let mut e = editor_with_rows(100);
+        e.current_buffer.cursor.y = 2;
+        e.scroll_viewport(20);
+        assert!(e.current_buffer.cursor.y as usize >= e.current_buffer.scroll_top);
+    }
+
+    #[test]
+    fn wheel_scroll_does_not_scroll_above_the_first_line() {
+        let mut e = editor_with_rows(10);
+        e.handle_mouse_event(mouse(MouseEventKind::ScrollUp, 0, 0));
+        assert_eq!(e.current_buffer.scroll_top, 0);
+    }
+
+    #[cfg(feature = "async-runtime")]
+    #[test]
+    fn on_tick_aborts_a_pending_sequence_once_it_times_out() {
+        let mut e = editor("foo");
+        e.config.key_sequence_timeout_ms = 1;
+        e.handle_normal_event(key('d'));
+        assert!(e.pending_nested.is_some());
+        std::thread::sleep(Duration::from_millis(5));
+        e.on_tick();
+        assert!(e.pending_nested.is_none());
+    }
+
+    #[cfg(feature = "async-runtime")]
+    #[test]
+    fn on_tick_does_not_auto_save_while_still_idle_under_the_interval() {
+        let mut e = editor("foo");
+        e.current_buffer.path = std::env::temp_dir()
+            .join("pep-test-on-tick-no-save.tmp")
+            .to_string_lossy()
+            .into_owned();
+        let _ = std::fs::remove_file(&e.current_buffer.path);
+        e.config.auto_save_interval_ms = Some(10_000);
+        e.current_buffer.insert("x".to_string());
+        e.on_tick();
+        assert!(e.current_buffer.modified);
+        assert!(!std::path::Path::new(&e.current_buffer.path).exists());
+    }
+
+    #[cfg(feature = "async-runtime")]
+    #[test]
+    fn on_tick_auto_saves_once_idle_past_the_interval_since_the_last_edit() {
+        let mut e = editor("foo");
+        e.current_buffer.path = std::env::temp_dir()
+            .join("pep-test-on-tick-save.tmp")
+            .to_string_lossy()
+            .into_owned();
+        let _ = std::fs::remove_file(&e.current_buffer.path);
+        e.config.auto_save_interval_ms = Some(1);
+        e.current_buffer.insert("x".to_string());
+        std::thread::sleep(Duration::from_millis(5));
+        e.on_tick();
+        assert!(!e.current_buffer.modified);
+        let _ = std::fs::remove_file(&e.current_buffer.path);
+    }
+
+    fn editor_with_rows(row_count: usize) -> Editor {
+        let lines: Vec<String> = (0..row_count).map(|i| format!("line{i}")).collect();
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        editor_lines(&refs)
+    }
+
+    #[test]
+    fn adjust_scroll_leaves_scroll_top_alone_while_the_cursor_stays_in_view() {
+        let mut e = editor_with_rows(5);
+        e.current_buffer.cursor.y = 3;
+        e.adjust_scroll();
+        assert_eq!(e.current_buffer.scroll_top, 0);
+    }
+
+    #[test]
+    fn adjust_scroll_follows_the_cursor_down_past_the_bottom_of_the_viewport() {
+        let mut e = editor_with_rows(100);
+        e.current_buffer.cursor.y = 50;
+        e.adjust_scroll();
+        assert!(e.current_buffer.scroll_top > 0);
+        assert!(e.current_buffer.cursor.y as usize >= e.current_buffer.scroll_top);
+    }
+
+    #[test]
+    fn adjust_scroll_follows_the_cursor_back_up_above_the_top_of_the_viewport() {
+        let mut e = editor_with_rows(100);
+        e.current_buffer.cursor.y = 50;
+        e.adjust_scroll();
+        e.current_buffer.cursor.y = 5;
+        e.adjust_scroll();
+        assert_eq!(e.current_buffer.scroll_top, 5);
+    }
+
+    #[test]
+    fn adjust_scroll_follows_the_cursor_horizontally_past_the_right_edge() {
+        let long_line = "x".repeat(200);
+        let mut e = editor(&long_line);
+        e.current_buffer.cursor.x = 150;
+        e.adjust_scroll();
+        assert!(e.current_buffer.scroll_left > 0);
+        assert!(e.current_buffer.cursor.x as usize >= e.current_buffer.scroll_left);
     }
 }
@@ -0,0 +1,18 @@
+use std::env;
+
+use anyhow::{Context, Result};
+
+mod clipboard;
+mod config;
+mod editor;
+mod window;
+
+use config::Config;
+use editor::Editor;
+
+fn main() -> Result<()> {
+    let path = env::args().nth(1).context("usage: pep <file>")?;
+    let config = Config::default();
+    let mut editor = Editor::new(config, path)?;
+    editor.run()
+}
@@ -0,0 +1,398 @@
+use std::fs;
+#[cfg(feature = "async-runtime")]
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::editor::Mode;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct Cursor {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// One buffer mutation, in a form that can be replayed either as a redo
+/// (`forward`) or undone by applying its opposite (`inverse`).
+#[derive(Debug, Clone)]
+enum EditOp {
+    InsertText { y: usize, x: usize, text: String },
+    RemoveText { y: usize, x: usize, len: usize },
+    InsertLine { y: usize, text: String },
+    RemoveLine { y: usize },
+    InsertBlock { y: usize, lines: Vec<String> },
+    RemoveBlock { y: usize, count: usize },
+}
+
+struct UndoRecord {
+    forward: EditOp,
+    inverse: EditOp,
+    cursor_before: Cursor,
+    cursor_after: Cursor,
+}
+
+pub struct Window {
+    pub path: String,
+    pub buffer: Vec<String>,
+    pub cursor: Cursor,
+    pub mode: Mode,
+    pub modified: bool,
+    pub render_buffer: bool,
+    /// Topmost buffer line currently shown in the viewport.
+    pub scroll_top: usize,
+    /// Leftmost buffer column currently shown in the viewport.
+    pub scroll_left: usize,
+    /// Set whenever Insert mode is (re-)entered; cleared once the first edit
+    /// of that session (a typed character, or the new line of `o`/`O`) has
+    /// been recorded. Stops `record` from
+    /// coalescing a new insert session's first character into the undo
+    /// group left by a previous one.
+    pub fresh_insert: bool,
+    /// When the buffer was last mutated, for the `async-runtime` auto-save
+    /// tick's "idle since the last edit" check.
+    #[cfg(feature = "async-runtime")]
+    pub last_edit: Instant,
+    undo_stack: Vec<UndoRecord>,
+    redo_stack: Vec<UndoRecord>,
+    undo_limit: usize,
+}
+
+impl Window {
+    pub fn new(path: String, undo_limit: usize) -> Result<Self> {
+        let buffer = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+                if lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![String::new()],
+            Err(e) => return Err(e).context(format!("failed to read {path}")),
+        };
+
+        Ok(Self {
+            path,
+            buffer,
+            cursor: Cursor::default(),
+            mode: Mode::Normal,
+            modified: false,
+            render_buffer: true,
+            scroll_top: 0,
+            scroll_left: 0,
+            fresh_insert: false,
+            #[cfg(feature = "async-runtime")]
+            last_edit: Instant::now(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            undo_limit,
+        })
+    }
+
+    /// Keep the cursor inside the buffer: `y` within the line count, `x`
+    /// within the current line (one past the end in Insert mode, since that's
+    /// where appended text lands).
+    pub fn clamp_cursor(&mut self) {
+        let max_y = self.buffer.len().saturating_sub(1) as u16;
+        if self.cursor.y > max_y {
+            self.cursor.y = max_y;
+        }
+
+        let line_len = self.buffer[self.cursor.y as usize].len();
+        let max_x = if self.mode == Mode::Insert {
+            line_len
+        } else {
+            line_len.saturating_sub(1)
+        } as u16;
+        if self.cursor.x > max_x {
+            self.cursor.x = max_x;
+        }
+    }
+
+    fn apply_op(&mut self, op: &EditOp) {
+        match op {
+            EditOp::InsertText { y, x, text } => {
+                let line = &mut self.buffer[*y];
+                let idx = (*x).min(line.len());
+                line.insert_str(idx, text);
+            }
+            EditOp::RemoveText { y, x, len } => {
+                let line = &mut self.buffer[*y];
+                let start = (*x).min(line.len());
+                let end = (*x + *len).min(line.len());
+                line.replace_range(start..end, "");
+            }
+            EditOp::InsertLine { y, text } => {
+                self.buffer.insert((*y).min(self.buffer.len()), text.clone());
+            }
+            EditOp::RemoveLine { y } => {
+                if *y < self.buffer.len() {
+                    self.buffer.remove(*y);
+                }
+                if self.buffer.is_empty() {
+                    self.buffer.push(String::new());
+                }
+            }
+            EditOp::InsertBlock { y, lines } => {
+                for (i, line) in lines.iter().enumerate() {
+                    self.buffer.insert((*y + i).min(self.buffer.len()), line.clone());
+                }
+            }
+            EditOp::RemoveBlock { y, count } => {
+                for _ in 0..*count {
+                    if *y < self.buffer.len() {
+                        self.buffer.remove(*y);
+                    }
+                }
+                if self.buffer.is_empty() {
+                    self.buffer.push(String::new());
+                }
+            }
+        }
+        self.modified = true;
+        self.render_buffer = true;
+        #[cfg(feature = "async-runtime")]
+        {
+            self.last_edit = Instant::now();
+        }
+    }
+
+    /// Push a completed edit onto the undo stack, clearing the redo stack.
+    /// Consecutive single-character inserts that directly extend the
+    /// previous one are coalesced into a single undo group, so one undo
+    /// reverts a whole typed run rather than one character. Coalescing never
+    /// crosses an Insert-mode session boundary: the first character typed
+    /// after (re-)entering Insert mode always starts a fresh group.
+    fn record(&mut self, forward: EditOp, inverse: EditOp, cursor_before: Cursor, cursor_after: Cursor) {
+        if self.mode == Mode::Insert && !self.fresh_insert {
+            if let EditOp::InsertText { y, x, text } = &forward {
+                if let Some(last) = self.undo_stack.last_mut() {
+                    if let EditOp::InsertText { y: ly, x: lx, text: ltext } = &mut last.forward {
+                        if *ly == *y && *lx + ltext.len() == *x {
+                            ltext.push_str(text);
+                            if let EditOp::RemoveText { len, .. } = &mut last.inverse {
+                                *len += text.len();
+                            }
+                            last.cursor_after = cursor_after;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        self.fresh_insert = false;
+
+        self.undo_stack.push(UndoRecord {
+            forward,
+            inverse,
+            cursor_before,
+            cursor_after,
+        });
+        if self.undo_stack.len() > self.undo_limit {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) {
+        let Some(record) = self.undo_stack.pop() else {
+            return;
+        };
+        self.apply_op(&record.inverse);
+        self.cursor = record.cursor_before;
+        self.redo_stack.push(record);
+    }
+
+    pub fn redo(&mut self) {
+        let Some(record) = self.redo_stack.pop() else {
+            return;
+        };
+        self.apply_op(&record.forward);
+        self.cursor = record.cursor_after;
+        self.undo_stack.push(record);
+    }
+
+    pub fn insert(&mut self, s: String) {
+        let x = self.cursor.x as usize;
+        let y = self.cursor.y as usize;
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::InsertText { y, x, text: s.clone() };
+        let inverse = EditOp::RemoveText { y, x, len: s.len() };
+        self.apply_op(&forward);
+        self.cursor.x += s.len() as u16;
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    pub fn insert_line_below(&mut self) {
+        let y = self.cursor.y as usize + 1;
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::InsertLine { y, text: String::new() };
+        let inverse = EditOp::RemoveLine { y };
+        self.apply_op(&forward);
+        self.cursor.y = y as u16;
+        self.cursor.x = 0;
+        self.mode = Mode::Insert;
+        self.fresh_insert = true;
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    pub fn insert_line_above(&mut self) {
+        let y = self.cursor.y as usize;
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::InsertLine { y, text: String::new() };
+        let inverse = EditOp::RemoveLine { y };
+        self.apply_op(&forward);
+        self.cursor.x = 0;
+        self.mode = Mode::Insert;
+        self.fresh_insert = true;
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    pub fn delete_under_cursor(&mut self) {
+        let x = self.cursor.x as usize;
+        let y = self.cursor.y as usize;
+        if x >= self.buffer[y].len() {
+            return;
+        }
+        let ch = self.buffer[y][x..x + 1].to_string();
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::RemoveText { y, x, len: 1 };
+        let inverse = EditOp::InsertText { y, x, text: ch };
+        self.apply_op(&forward);
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    pub fn delete_line(&mut self) {
+        let y = self.cursor.y as usize;
+        let removed = self.buffer[y].clone();
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::RemoveLine { y };
+        let inverse = EditOp::InsertLine { y, text: removed };
+        self.apply_op(&forward);
+
+        if self.cursor.y as usize >= self.buffer.len() {
+            self.cursor.y = self.buffer.len() as u16 - 1;
+        }
+        self.cursor.x = 0;
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    /// Insert linewise register contents as whole lines starting at `y`.
+    pub fn put_lines(&mut self, y: usize, text: &str) {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            return;
+        }
+        let cursor_before = self.cursor;
+
+        let forward = EditOp::InsertBlock { y, lines: lines.clone() };
+        let inverse = EditOp::RemoveBlock { y, count: lines.len() };
+        self.apply_op(&forward);
+        self.cursor.y = y as u16;
+        self.cursor.x = 0;
+
+        self.record(forward, inverse, cursor_before, self.cursor);
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        fs::write(&self.path, self.buffer.join("\n") + "\n")
+            .with_context(|| format!("failed to save {}", self.path))?;
+        self.modified = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> Window {
+        Window::new("pep-test-nonexistent-file.tmp".to_string(), 100).unwrap()
+    }
+
+    #[test]
+    fn insert_then_undo_restores_the_buffer() {
+        let mut w = window();
+        w.insert("hello".to_string());
+        assert_eq!(w.buffer[0], "hello");
+        w.undo();
+        assert_eq!(w.buffer[0], "");
+        assert_eq!(w.cursor, Cursor::default());
+    }
+
+    #[test]
+    fn inserting_a_multibyte_char_advances_the_cursor_by_its_byte_length() {
+        let mut w = window();
+        w.insert("é".to_string());
+        assert_eq!(w.cursor.x as usize, "é".len());
+        w.insert("x".to_string());
+        assert_eq!(w.buffer[0], "éx");
+    }
+
+    #[test]
+    fn undo_then_redo_reapplies_the_edit() {
+        let mut w = window();
+        w.insert("hi".to_string());
+        w.undo();
+        w.redo();
+        assert_eq!(w.buffer[0], "hi");
+    }
+
+    #[test]
+    fn consecutive_inserts_in_one_session_coalesce_into_one_undo() {
+        let mut w = window();
+        w.mode = Mode::Insert;
+        w.fresh_insert = true;
+        w.insert("a".to_string());
+        w.insert("b".to_string());
+        w.insert("c".to_string());
+        assert_eq!(w.buffer[0], "abc");
+
+        w.undo();
+        assert_eq!(w.buffer[0], "", "one undo should revert the whole typed run");
+    }
+
+    #[test]
+    fn a_new_insert_session_does_not_coalesce_with_the_previous_one() {
+        let mut w = window();
+        w.mode = Mode::Insert;
+        w.fresh_insert = true;
+        w.insert("abc".to_string());
+
+        w.mode = Mode::Normal;
+        w.cursor.x = 0;
+        w.mode = Mode::Insert;
+        w.fresh_insert = true;
+        w.insert("def".to_string());
+
+        assert_eq!(w.buffer[0], "defabc");
+        w.undo();
+        assert_eq!(w.buffer[0], "abc", "undo should only remove the second session's text");
+        w.undo();
+        assert_eq!(w.buffer[0], "");
+    }
+
+    #[test]
+    fn delete_line_then_undo_restores_the_line() {
+        let mut w = window();
+        w.insert("one".to_string());
+        w.insert_line_below();
+        w.insert("two".to_string());
+        w.delete_line();
+        assert_eq!(w.buffer, vec!["one".to_string()]);
+
+        w.undo();
+        assert_eq!(w.buffer, vec!["one".to_string(), "two".to_string()]);
+    }
+}